@@ -0,0 +1,30 @@
+/* Hand-maintained bindings for the cgo c-archive exported by go/js_build.go.
+   Target layout: linux. These are NOT bindgen output; a normal build
+   regenerates from the freshly compiled libgo.h. They are used only for the
+   `nobuild`/`docs-only` path, where neither Go nor bindgen runs, and the
+   `vendored-bindings` path, where Go still builds the archive but bindgen is
+   skipped. Keep the signatures below in sync with the Go `//export` decls. */
+
+pub type GoInt8 = ::std::os::raw::c_schar;
+pub type GoUint8 = ::std::os::raw::c_uchar;
+pub type GoInt64 = ::std::os::raw::c_longlong;
+pub type GoInt = GoInt64;
+pub type GoUintptr = ::std::os::raw::c_ulong;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _GoString_ {
+    pub p: *const ::std::os::raw::c_char,
+    pub n: isize,
+}
+pub type GoString = _GoString_;
+
+extern "C" {
+    /// Bundle the entrypoint described by the JSON request `input` and return a
+    /// newly allocated C string holding the JSON response. The caller owns the
+    /// returned pointer and must free it with [`FreeBuildResult`].
+    pub fn BuildJs(input: *mut ::std::os::raw::c_char) -> *mut ::std::os::raw::c_char;
+}
+extern "C" {
+    pub fn FreeBuildResult(ptr: *mut ::std::os::raw::c_char);
+}
@@ -1,45 +1,246 @@
 // https://amirmalik.net/2023/02/15/embedding-go-in-rust
 extern crate bindgen;
 
+mod gobuild;
+
+use gobuild::Build;
 use std::env;
-use std::path::PathBuf;
-use std::process::Command;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 fn main() {
     let out_dir = env::var("OUT_DIR").unwrap();
     let out_path = PathBuf::from(&out_dir);
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+
+    // When the `sidecar` feature is enabled we do not static-link Go into the
+    // crate at all. Instead we compile `./go/js_build.go` into a standalone
+    // executable and talk to it over stdin/stdout at runtime (see
+    // `src/sidecar.rs`). This is the portable fallback on toolchains where
+    // cgo c-archive linking is fragile (golang/go#13492).
+    if env::var_os("CARGO_FEATURE_SIDECAR").is_some() {
+        let exe = out_path.join(sidecar_binary_name());
+
+        Build::new().file("./go/js_build.go").compile(&exe);
+
+        // The sidecar build links nothing into the crate and needs no bindgen
+        // pass; hand the executable path to the runtime through a compile-time
+        // env var so `src/sidecar.rs` knows what to spawn.
+        println!("cargo:rustc-env=MOUNTAINEER_JSBUILD_BIN={}", exe.display());
+        track_go_sources();
+        return;
+    }
+
+    // Docs.rs and many CI images have neither a Go toolchain nor libclang. In
+    // those environments (`nobuild`/`docs-only` feature, or `DOCS_RS` set) we
+    // skip both the Go compilation and bindgen and `include!` the checked-in
+    // bindings for the target OS — the approach magick-rust and proj-sys take.
+    let nobuild = env::var_os("CARGO_FEATURE_NOBUILD").is_some()
+        || env::var_os("CARGO_FEATURE_DOCS_ONLY").is_some()
+        || env::var_os("DOCS_RS").is_some();
+    if nobuild {
+        copy_pregenerated(&manifest_dir, &target_os, &out_path);
+        println!("cargo:rerun-if-env-changed=DOCS_RS");
+        return;
+    }
 
     // Step 1: Compile the Go code into a static library.
-    let status = Command::new("go")
-        .args([
-            "build",
-            "-buildmode=c-archive",
-            "-o",
-            out_path.join("libgo.a").to_str().unwrap(),
-            "./go/js_build.go",
-        ])
-        .status()
-        .expect("Failed to execute go build");
-
-    assert!(status.success(), "Go build failed");
-
-    // Step 2: Generate Rust bindings using bindgen.
-    let bindings = bindgen::Builder::default()
-        .header(out_path.join("libgo.h").to_str().unwrap())
-        .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
-        .generate()
-        .expect("Unable to generate bindings");
+    Build::new()
+        .buildmode("c-archive")
+        .file("./go/js_build.go")
+        .compile(&out_path.join("libgo.a"));
+
+    // Step 2: Obtain the Rust bindings. A normal build regenerates them from
+    // the freshly compiled `libgo.h` so bindgen catches any ABI/signature
+    // drift — we only fall back to the committed per-platform file when the
+    // caller opts in with `vendored-bindings` (the same file `nobuild` uses
+    // when no toolchain is available).
+    let vendored = env::var_os("CARGO_FEATURE_VENDORED_BINDINGS").is_some();
 
-    bindings
-        .write_to_file(out_path.join("bindings.rs"))
-        .expect("Couldn't write bindings!");
+    if vendored {
+        copy_pregenerated(&manifest_dir, &target_os, &out_path);
+    } else {
+        let bindings = configure_bindgen(out_path.join("libgo.h"))
+            .generate()
+            .expect("Unable to generate bindings");
+
+        bindings
+            .write_to_file(out_path.join("bindings.rs"))
+            .expect("Couldn't write bindings!");
+    }
 
-    // Inform Cargo about the dependencies and how to link the library.
-    println!("cargo:rerun-if-changed=go/list_struct.go");
+    // Inform Cargo about the dependencies and how to link the library. Walk the
+    // whole `go/` tree so any edit to the compiled sources triggers a rebuild,
+    // not just the one (stale) file the original `rerun-if-changed` named.
+    track_go_sources();
     println!("cargo:rustc-link-search=native={}", out_dir);
     println!("cargo:rustc-link-lib=static=go");
 
-    if cfg!(target_os = "macos") {
-        println!("cargo:rustc-link-lib=framework=CoreFoundation");
+    emit_platform_links(&target_os);
+}
+
+/// Emit the per-target link directives a cgo c-archive needs so the crate
+/// links out of the box, without a side `.cargo/config.toml`.
+///
+/// The runtime dependencies of the Go c-archive differ by platform: macOS
+/// needs `CoreFoundation` *and* `Security`, Linux needs `pthread`/`dl`, and
+/// Windows needs `ws2_32`/`userenv`/`bcrypt`. The set is overridable through
+/// `MOUNTAINEER_LINK_LIBS` (whitespace/comma separated) for unusual
+/// toolchains; on macOS entries without a `framework=`/`static=` prefix are
+/// treated as frameworks.
+fn emit_platform_links(target_os: &str) {
+    if let Ok(custom) = env::var("MOUNTAINEER_LINK_LIBS") {
+        let is_macos = target_os == "macos";
+        for lib in custom.split(|c: char| c.is_whitespace() || c == ',') {
+            let lib = lib.trim();
+            if lib.is_empty() {
+                continue;
+            }
+            if is_macos && !lib.contains('=') {
+                println!("cargo:rustc-link-lib=framework={}", lib);
+            } else {
+                println!("cargo:rustc-link-lib={}", lib);
+            }
+        }
+        return;
+    }
+
+    match target_os {
+        "macos" | "ios" => {
+            println!("cargo:rustc-link-lib=framework=CoreFoundation");
+            println!("cargo:rustc-link-lib=framework=Security");
+        }
+        "linux" | "android" => {
+            println!("cargo:rustc-link-lib=dylib=pthread");
+            println!("cargo:rustc-link-lib=dylib=dl");
+        }
+        "windows" => {
+            println!("cargo:rustc-link-lib=dylib=ws2_32");
+            println!("cargo:rustc-link-lib=dylib=userenv");
+            println!("cargo:rustc-link-lib=dylib=bcrypt");
+        }
+        _ => {}
+    }
+}
+
+/// Name of the standalone js-build executable produced in `sidecar` mode.
+fn sidecar_binary_name() -> &'static str {
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows") {
+        "mountaineer-jsbuild.exe"
+    } else {
+        "mountaineer-jsbuild"
+    }
+}
+
+/// Path to the committed `bindings_<os>.rs` for `target_os`, if it exists.
+fn pregenerated_path(manifest_dir: &Path, target_os: &str) -> Option<PathBuf> {
+    let candidate = manifest_dir.join(format!("bindings_{}.rs", bindings_os(target_os)));
+    candidate.exists().then_some(candidate)
+}
+
+/// Copy the committed bindings for `target_os` into `OUT_DIR/bindings.rs`, so
+/// `src/lib.rs` can `include!` them through the usual `OUT_DIR` path.
+fn copy_pregenerated(manifest_dir: &Path, target_os: &str, out_path: &Path) {
+    let src = pregenerated_path(manifest_dir, target_os).unwrap_or_else(|| {
+        panic!(
+            "no pre-generated bindings_{}.rs found; build once from source \
+             (without `vendored-bindings`/`nobuild`) and commit the result",
+            bindings_os(target_os)
+        )
+    });
+    fs::copy(&src, out_path.join("bindings.rs")).expect("Couldn't copy pre-generated bindings");
+    println!("cargo:rerun-if-changed={}", src.display());
+}
+
+/// Normalize Cargo's `target_os` to the suffix used by the committed files.
+fn bindings_os(target_os: &str) -> &str {
+    match target_os {
+        "macos" => "macos",
+        "windows" => "windows",
+        // Linux and the other ELF/glibc-ish targets share a layout.
+        _ => "linux",
     }
 }
+
+/// Emit a `rerun-if-changed` line for every `.go` source under `go/`, plus the
+/// module files, so any change to the compiled Go reliably rebuilds.
+fn track_go_sources() {
+    // Always anchor on this script and the `go/` path itself. If the directory
+    // walk below finds nothing (missing/renamed tree), these still keep Cargo
+    // off its coarse whole-package heuristic and make the tracked set explicit.
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=go");
+
+    for meta in ["go.mod", "go.sum"] {
+        let path = Path::new("go").join(meta);
+        if path.exists() {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+
+    if !track_go_dir(Path::new("go")) {
+        println!(
+            "cargo:warning=could not read the `go/` source directory; \
+             incremental rebuild tracking may be incomplete"
+        );
+    }
+}
+
+/// Recursively walk `dir`, emitting a `rerun-if-changed` line per `.go` file.
+/// Returns `false` if `dir` itself could not be read, so the caller can warn.
+fn track_go_dir(dir: &Path) -> bool {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            track_go_dir(&path);
+        } else if path.extension().and_then(|e| e.to_str()) == Some("go") {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+    true
+}
+
+/// Build the `bindgen::Builder` for the Go c-archive header, applying the
+/// optional allowlist/blocklist and `no_std` tweaks other cgo crates rely on
+/// (notcurses, DragonOS).
+///
+/// This runs on every from-source build (the committed `bindings_<os>.rs` are
+/// only used under `vendored-bindings`/`nobuild`), so the blocklist below
+/// always applies; the `minimal-bindings` and `no-std` feature gates shape the
+/// generated file accordingly. Regenerate and recommit the vendored files with
+/// the matching feature set if a consumer needs these reflected there too.
+fn configure_bindgen(header: PathBuf) -> bindgen::Builder {
+    let mut builder = bindgen::Builder::default()
+        .header(header.to_str().unwrap())
+        .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
+        // Keep doc comments from the generated C header on the Rust items.
+        .generate_comments(true)
+        // `long double` shims bindgen can't represent on every platform; they
+        // surface as the classic `u128` ABI warnings, so drop them outright.
+        .blocklist_function("strtold")
+        .blocklist_function("wcstold")
+        .blocklist_function("qecvt.*")
+        .blocklist_function("qfcvt.*")
+        .blocklist_function("qgcvt");
+
+    // Restrict generation to the exported cgo symbols when asked, keeping the
+    // surface small for downstreams that only call into the archive.
+    if env::var_os("CARGO_FEATURE_MINIMAL_BINDINGS").is_some() {
+        builder = builder
+            .allowlist_function("Build.*")
+            .allowlist_function("Free.*")
+            .allowlist_type("Go.*");
+    }
+
+    // `no_std` consumers need `core::ffi` types instead of `std::os::raw`.
+    if env::var_os("CARGO_FEATURE_NO_STD").is_some() {
+        builder = builder.use_core().ctypes_prefix("::core::ffi");
+    }
+
+    builder
+}
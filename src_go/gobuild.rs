@@ -0,0 +1,239 @@
+//! A small `go build` driver in the spirit of the `gobuild`/`cc` crates.
+//!
+//! Instead of hardcoding a single `Command::new("go")` invocation, `build.rs`
+//! configures a [`Build`] — the set of `.go` sources, build tags, extra env,
+//! the `-buildmode`, and whether cgo is enabled — and calls [`Build::compile`].
+//! The driver sets `CGO_ENABLED`, derives `GOOS`/`GOARCH` from Cargo's target,
+//! and defaults `GOCACHE` into `OUT_DIR` so sandboxed/offline builds (docs.rs)
+//! don't fail trying to write under `$HOME`.
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Configuration for one `go build` invocation.
+pub struct Build {
+    files: Vec<PathBuf>,
+    tags: Vec<String>,
+    envs: Vec<(OsString, OsString)>,
+    buildmode: Option<String>,
+    cgo: bool,
+    gocache: Option<PathBuf>,
+}
+
+impl Build {
+    /// Start a new build with cgo enabled (the common case for this crate).
+    pub fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            tags: Vec::new(),
+            envs: Vec::new(),
+            buildmode: None,
+            cgo: true,
+            gocache: None,
+        }
+    }
+
+    /// Add a single `.go` source.
+    pub fn file(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.files.push(path.into());
+        self
+    }
+
+    /// Add several `.go` sources at once.
+    pub fn files<I, P>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.files.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add a build tag (forwarded as part of `-tags=...`).
+    pub fn tag(&mut self, tag: impl Into<String>) -> &mut Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Set an extra environment variable for the `go` process.
+    pub fn env(&mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> &mut Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set `-buildmode=<mode>` (e.g. `c-archive`). Omitted by default.
+    pub fn buildmode(&mut self, mode: impl Into<String>) -> &mut Self {
+        self.buildmode = Some(mode.into());
+        self
+    }
+
+    /// Toggle `CGO_ENABLED`.
+    pub fn cgo(&mut self, enabled: bool) -> &mut Self {
+        self.cgo = enabled;
+        self
+    }
+
+    /// Override `GOCACHE`; defaults into `OUT_DIR` when left unset.
+    pub fn gocache(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.gocache = Some(dir.into());
+        self
+    }
+
+    /// Run `go build -o <output> …`, panicking with the exit status on failure.
+    pub fn compile(&self, output: &Path) {
+        let go = go_bin();
+        check_go_version(&go);
+
+        let mut cmd = Command::new(&go);
+        cmd.arg("build");
+
+        if let Some(mode) = &self.buildmode {
+            cmd.arg(format!("-buildmode={}", mode));
+        }
+        if !self.tags.is_empty() {
+            cmd.arg(format!("-tags={}", self.tags.join(",")));
+        }
+        cmd.arg("-o").arg(output);
+        cmd.args(&self.files);
+
+        cmd.env("CGO_ENABLED", if self.cgo { "1" } else { "0" });
+        if let Some(goos) = cargo_goos() {
+            cmd.env("GOOS", goos);
+        }
+        if let Some(goarch) = cargo_goarch() {
+            cmd.env("GOARCH", goarch);
+        }
+
+        let gocache = self
+            .gocache
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(env::var("OUT_DIR").unwrap()).join("gocache"));
+        cmd.env("GOCACHE", &gocache);
+
+        for (key, value) in &self.envs {
+            cmd.env(key, value);
+        }
+
+        let status = cmd.status().expect("Failed to execute go build");
+        assert!(status.success(), "Go build failed: {}", status);
+    }
+}
+
+impl Default for Build {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The lowest Go toolchain we're willing to build against. Overridable via the
+/// `GO_MIN_VERSION` env var (e.g. `1.21`).
+const DEFAULT_MIN_GO_VERSION: (u32, u32) = (1, 18);
+
+/// Locate the `go` binary, honoring a `GO_BIN` override.
+///
+/// On Unix we resolve the binary through `which` so a clear error surfaces
+/// here rather than as a mysterious bindgen/link failure later; GUI-launched
+/// and minimal environments frequently have an empty `PATH`. On Windows we
+/// fall back to a bare `"go"` and let `CreateProcess` search.
+fn go_bin() -> OsString {
+    if let Some(explicit) = env::var_os("GO_BIN") {
+        return explicit;
+    }
+
+    if cfg!(windows) {
+        return OsStr::new("go").to_os_string();
+    }
+
+    let resolved = Command::new("which")
+        .arg("go")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|s| !s.is_empty());
+
+    match resolved {
+        Some(path) => OsString::from(path),
+        None => {
+            println!("cargo:warning=could not find the `go` binary on PATH");
+            panic!(
+                "Go toolchain not found: install Go and ensure it is on PATH, \
+                 or set GO_BIN to its location"
+            );
+        }
+    }
+}
+
+/// Run `go version` and fail early with a comprehensible diagnostic if the
+/// toolchain is missing or older than the configured minimum.
+fn check_go_version(go: &OsStr) {
+    let output = Command::new(go)
+        .arg("version")
+        .output()
+        .unwrap_or_else(|e| {
+            println!("cargo:warning=failed to run `{} version`", go.to_string_lossy());
+            panic!("could not execute the Go toolchain ({}): install Go or set GO_BIN", e);
+        });
+    assert!(output.status.success(), "`go version` exited with {}", output.status);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let min = min_go_version();
+    match parse_go_version(&stdout) {
+        Some(found) if found >= min => {}
+        Some(found) => panic!(
+            "Go {}.{} is too old; mountaineer needs at least Go {}.{} \
+             (set GO_MIN_VERSION to override)",
+            found.0, found.1, min.0, min.1
+        ),
+        None => println!(
+            "cargo:warning=could not parse Go version from {:?}; proceeding",
+            stdout.trim()
+        ),
+    }
+}
+
+/// The minimum acceptable Go version, from `GO_MIN_VERSION` or the default.
+fn min_go_version() -> (u32, u32) {
+    env::var("GO_MIN_VERSION")
+        .ok()
+        .and_then(|v| parse_version_pair(&v))
+        .unwrap_or(DEFAULT_MIN_GO_VERSION)
+}
+
+/// Pull the `(major, minor)` out of a `go version go1.21.4 …` line.
+fn parse_go_version(text: &str) -> Option<(u32, u32)> {
+    let token = text.split_whitespace().find(|t| t.starts_with("go1"))?;
+    parse_version_pair(token.trim_start_matches("go"))
+}
+
+/// Parse a `"<major>.<minor>"` (ignoring any trailing patch) into a pair.
+fn parse_version_pair(s: &str) -> Option<(u32, u32)> {
+    let mut parts = s.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().unwrap_or("0").parse().ok()?;
+    Some((major, minor))
+}
+
+/// Map Cargo's `target_os` to the matching `GOOS`.
+fn cargo_goos() -> Option<&'static str> {
+    match env::var("CARGO_CFG_TARGET_OS").ok()?.as_str() {
+        "macos" | "ios" => Some("darwin"),
+        "windows" => Some("windows"),
+        "linux" => Some("linux"),
+        "freebsd" => Some("freebsd"),
+        _ => None,
+    }
+}
+
+/// Map Cargo's `target_arch` to the matching `GOARCH`.
+fn cargo_goarch() -> Option<&'static str> {
+    match env::var("CARGO_CFG_TARGET_ARCH").ok()?.as_str() {
+        "x86_64" => Some("amd64"),
+        "aarch64" => Some("arm64"),
+        "x86" => Some("386"),
+        "arm" => Some("arm"),
+        _ => None,
+    }
+}
@@ -0,0 +1,243 @@
+//! Out-of-process js-build backend used when the crate is compiled with the
+//! `sidecar` feature.
+//!
+//! Instead of static-linking the Go archive through FFI, `build.rs` compiles
+//! `go/js_build.go` into a standalone executable and records its path in the
+//! `MOUNTAINEER_JSBUILD_BIN` compile-time env var. We spawn that executable
+//! once and keep it alive, exchanging length-prefixed JSON frames over its
+//! stdin/stdout: a 4-byte big-endian length followed by a UTF-8 JSON body.
+//!
+//! Requests carry a monotonically increasing `id`. A dedicated reader thread
+//! demultiplexes responses by that id back to the waiting caller, so several
+//! builds can be in flight at once — the write lock is only held long enough
+//! to push one frame, never across the read. The child is respawned
+//! transparently if it dies.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Path to the js-build executable, baked in by `build.rs`.
+const JSBUILD_BIN: &str = env!("MOUNTAINEER_JSBUILD_BIN");
+
+/// A build request sent to the sidecar process.
+#[derive(Serialize)]
+struct Request<'a> {
+    id: u64,
+    entry: &'a str,
+    options: &'a Value,
+}
+
+/// A build response read back from the sidecar process.
+#[derive(Deserialize)]
+struct Response {
+    id: u64,
+    ok: bool,
+    output: Option<String>,
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+/// In-flight requests keyed by id, each waiting on a one-shot channel.
+type Pending = Arc<Mutex<HashMap<u64, Sender<io::Result<Response>>>>>;
+
+/// A single long-lived child process plus the state needed to route responses.
+struct Conn {
+    stdin: Mutex<ChildStdin>,
+    pending: Pending,
+    alive: Arc<AtomicBool>,
+    child: Mutex<Child>,
+    _reader: JoinHandle<()>,
+}
+
+impl Conn {
+    fn spawn() -> io::Result<Arc<Self>> {
+        let mut child = Command::new(PathBuf::from(JSBUILD_BIN))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("piped stdin");
+        let stdout = child.stdout.take().expect("piped stdout");
+
+        let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+        let alive = Arc::new(AtomicBool::new(true));
+
+        let reader = {
+            let pending = Arc::clone(&pending);
+            let alive = Arc::clone(&alive);
+            thread::spawn(move || reader_loop(stdout, pending, alive))
+        };
+
+        Ok(Arc::new(Self {
+            stdin: Mutex::new(stdin),
+            pending,
+            alive,
+            child: Mutex::new(child),
+            _reader: reader,
+        }))
+    }
+
+    /// Send one framed request and block until its matching response arrives.
+    /// Holds the write lock only while pushing the frame; the wait happens with
+    /// no locks held, so other callers can write and complete concurrently.
+    fn request(&self, id: u64, request: &Request) -> io::Result<Response> {
+        if !self.alive.load(Ordering::Acquire) {
+            return Err(broken_pipe());
+        }
+
+        let (tx, rx) = channel();
+        self.pending.lock().unwrap().insert(id, tx);
+
+        let body = serde_json::to_vec(request)?;
+        {
+            let mut stdin = self.stdin.lock().unwrap();
+            if let Err(e) = write_frame(&mut *stdin, &body) {
+                self.pending.lock().unwrap().remove(&id);
+                return Err(e);
+            }
+        }
+
+        // The reader thread resolves the channel; if it's gone the child died.
+        rx.recv().unwrap_or_else(|_| Err(broken_pipe()))
+    }
+}
+
+impl Drop for Conn {
+    fn drop(&mut self) {
+        if let Ok(mut child) = self.child.lock() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Read frames off the child's stdout and hand each one to the caller waiting
+/// on its id. On EOF or a malformed frame, mark the connection dead and wake
+/// every remaining waiter with an error.
+fn reader_loop(mut stdout: ChildStdout, pending: Pending, alive: Arc<AtomicBool>) {
+    loop {
+        let body = match read_frame(&mut stdout) {
+            Ok(body) => body,
+            Err(_) => break,
+        };
+        match serde_json::from_slice::<Response>(&body) {
+            Ok(resp) => {
+                if let Some(tx) = pending.lock().unwrap().remove(&resp.id) {
+                    let _ = tx.send(Ok(resp));
+                }
+            }
+            // A frame we can't parse has no id to route by; treat as fatal.
+            Err(_) => break,
+        }
+    }
+
+    alive.store(false, Ordering::Release);
+    for (_, tx) in pending.lock().unwrap().drain() {
+        let _ = tx.send(Err(broken_pipe()));
+    }
+}
+
+/// Handle to the js-build sidecar. Hold one per [`crate`] instance and share
+/// it across threads; builds are demultiplexed by request id.
+pub struct Sidecar {
+    conn: Mutex<Option<Arc<Conn>>>,
+    next_id: AtomicU64,
+}
+
+impl Sidecar {
+    /// Create a handle. The child is spawned lazily on the first build.
+    pub fn new() -> Self {
+        Self {
+            conn: Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Build `entry` with the given esbuild `options`, returning the bundled
+    /// output or the errors the bundler reported.
+    pub fn build(&self, entry: &str, options: &Value) -> io::Result<Result<String, Vec<String>>> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = Request { id, entry, options };
+
+        // Try on the live connection, respawning and retrying once if the child
+        // crashed between requests.
+        match self.conn()?.request(id, &request) {
+            Ok(resp) => Ok(response_into_result(resp)),
+            Err(ref e) if is_broken_pipe(e) => {
+                self.reset();
+                let resp = self.conn()?.request(id, &request)?;
+                Ok(response_into_result(resp))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Return the live connection, spawning a fresh child if none exists or the
+    /// current one has died.
+    fn conn(&self) -> io::Result<Arc<Conn>> {
+        let mut guard = self.conn.lock().unwrap();
+        if let Some(conn) = guard.as_ref() {
+            if conn.alive.load(Ordering::Acquire) {
+                return Ok(Arc::clone(conn));
+            }
+        }
+        let conn = Conn::spawn()?;
+        *guard = Some(Arc::clone(&conn));
+        Ok(conn)
+    }
+
+    /// Drop the current connection so the next build spawns a fresh child.
+    fn reset(&self) {
+        *self.conn.lock().unwrap() = None;
+    }
+}
+
+impl Default for Sidecar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn response_into_result(resp: Response) -> Result<String, Vec<String>> {
+    if resp.ok {
+        Ok(resp.output.unwrap_or_default())
+    } else {
+        Err(resp.errors)
+    }
+}
+
+/// Write a length-prefixed frame: 4-byte big-endian length, then `body`.
+fn write_frame(w: &mut impl Write, body: &[u8]) -> io::Result<()> {
+    w.write_all(&(body.len() as u32).to_be_bytes())?;
+    w.write_all(body)?;
+    w.flush()
+}
+
+/// Read a frame written by [`write_frame`].
+fn read_frame(r: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    r.read_exact(&mut len)?;
+    let mut body = vec![0u8; u32::from_be_bytes(len) as usize];
+    r.read_exact(&mut body)?;
+    Ok(body)
+}
+
+fn broken_pipe() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "sidecar process exited")
+}
+
+fn is_broken_pipe(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::BrokenPipe | io::ErrorKind::UnexpectedEof
+    )
+}